@@ -0,0 +1,95 @@
+use axum::body::Body;
+use http::{HeaderMap, Response, StatusCode};
+use std::error::Error;
+use std::fmt;
+
+/// The error type returned by the governor middleware when a request cannot be
+/// served, and fed to the configured error handler to produce a [`Response`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum GovernorError {
+    /// The quota for the request's key has been exhausted. `wait_time` is the
+    /// number of milliseconds the client should wait before retrying.
+    TooManyRequests {
+        wait_time: u64,
+        headers: Option<HeaderMap>,
+    },
+    /// The request's cost exceeds the configured burst size, so it can never be
+    /// satisfied regardless of how long the client waits.
+    InsufficientCapacity { cost: u32, burst_size: u32 },
+    /// The key extractor was unable to derive a key from the request.
+    UnableToExtractKey,
+    /// An arbitrary error produced by a key extractor or middleware.
+    Other {
+        code: StatusCode,
+        msg: Option<String>,
+        headers: Option<HeaderMap>,
+    },
+}
+
+impl GovernorError {
+    /// Convert the error into a valid http [`Response`].
+    pub fn as_response(&mut self) -> Response<Body> {
+        match self {
+            GovernorError::TooManyRequests { wait_time, headers } => {
+                let response = Response::new(Body::from(format!(
+                    "Too Many Requests! Wait for {wait_time}ms"
+                )));
+                let (mut parts, body) = response.into_parts();
+                parts.status = StatusCode::TOO_MANY_REQUESTS;
+                if let Some(headers) = headers.take() {
+                    parts.headers = headers;
+                }
+                Response::from_parts(parts, body)
+            }
+            GovernorError::InsufficientCapacity { cost, burst_size } => {
+                let response = Response::new(Body::from(format!(
+                    "Request cost {cost} exceeds the burst size of {burst_size}"
+                )));
+                let (mut parts, body) = response.into_parts();
+                parts.status = StatusCode::PAYLOAD_TOO_LARGE;
+                Response::from_parts(parts, body)
+            }
+            GovernorError::UnableToExtractKey => {
+                let response = Response::new(Body::from("Unable To Extract Key!"));
+                let (mut parts, body) = response.into_parts();
+                parts.status = StatusCode::INTERNAL_SERVER_ERROR;
+                Response::from_parts(parts, body)
+            }
+            GovernorError::Other { code, msg, headers } => {
+                let response = Response::new(Body::from(msg.clone().unwrap_or_default()));
+                let (mut parts, body) = response.into_parts();
+                parts.status = *code;
+                if let Some(headers) = headers.take() {
+                    parts.headers = headers;
+                }
+                Response::from_parts(parts, body)
+            }
+        }
+    }
+}
+
+impl fmt::Display for GovernorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GovernorError::TooManyRequests { wait_time, .. } => {
+                write!(f, "Too many requests, wait for {wait_time}ms")
+            }
+            GovernorError::InsufficientCapacity { cost, burst_size } => {
+                write!(
+                    f,
+                    "Request cost {cost} exceeds the burst size of {burst_size}"
+                )
+            }
+            GovernorError::UnableToExtractKey => write!(f, "Unable to extract key"),
+            GovernorError::Other { code, msg, .. } => {
+                write!(f, "Other error: code {code}")?;
+                if let Some(msg) = msg {
+                    write!(f, ", {msg}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for GovernorError {}
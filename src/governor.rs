@@ -1,16 +1,35 @@
 use crate::GovernorError;
+use arc_swap::ArcSwap;
 use axum::body::Body;
-use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
-use http::{Method, Response};
-use std::{fmt, num::NonZeroU32, sync::Arc, time::Duration};
+use governor::clock::DefaultClock;
+use governor::middleware::StateInformationMiddleware;
+use governor::state::{InMemoryState, NotKeyed};
+use governor::{Jitter, Quota, RateLimiter};
+use http::{Method, Request, Response};
+use std::{collections::HashMap, fmt, num::NonZeroU32, sync::Arc, time::Duration};
 
 pub const DEFAULT_PERIOD: Duration = Duration::from_millis(500);
 pub const DEFAULT_BURST_SIZE: u32 = 8;
 
 // Required by Governor's RateLimiter to share it across threads
 // See Governor User Guide: https://docs.rs/governor/0.6.0/governor/_guide/index.html
-// pub type SharedRateLimiter<M> = Arc<RateLimiter<NotKeyed, InMemoryState, DefaultClock, M>>;
-pub type SharedRateLimiter = Arc<DefaultDirectRateLimiter>;
+// The StateInformationMiddleware lets `check`/`check_n` return a StateSnapshot,
+// which the layer uses to report the remaining budget on allowed responses.
+pub type SharedRateLimiter =
+    Arc<RateLimiter<NotKeyed, InMemoryState, DefaultClock, StateInformationMiddleware>>;
+
+/// Build a shared limiter from a period and burst size. Both are assumed to be
+/// non-zero; callers validate that before constructing the quota.
+fn build_limiter(period: Duration, burst_size: u32) -> SharedRateLimiter {
+    Arc::new(
+        RateLimiter::direct(
+            Quota::with_period(period)
+                .unwrap()
+                .allow_burst(NonZeroU32::new(burst_size).unwrap()),
+        )
+        .with_middleware::<StateInformationMiddleware>(),
+    )
+}
 
 /// Helper struct for building a configuration for the governor middleware.
 ///
@@ -46,12 +65,87 @@ pub struct GovernorConfigBuilder {
     burst_size: u32,
     methods: Option<Vec<Method>>,
     error_handler: ErrorHandler,
+    request_cost: RequestCost,
+    use_headers: bool,
+    jitter: Option<Jitter>,
+    tiers: Vec<(String, Duration, u32)>,
+    classifier: Classifier,
 }
 
 // function for handling GovernorError and produce valid http Response type.
 #[derive(Clone)]
 struct ErrorHandler(Arc<dyn Fn(GovernorError) -> Response<Body> + Send + Sync>);
 
+// function computing how many cells of the quota a request consumes.
+#[derive(Clone)]
+struct RequestCost(Arc<dyn Fn(&Request<Body>) -> u32 + Send + Sync>);
+
+impl Default for RequestCost {
+    fn default() -> Self {
+        // Every request costs a single cell, preserving the pre-cost behavior.
+        Self(Arc::new(|_| 1))
+    }
+}
+
+impl fmt::Debug for RequestCost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestCost").finish()
+    }
+}
+
+impl PartialEq for RequestCost {
+    fn eq(&self, _: &Self) -> bool {
+        // there is no easy way to tell two object equals.
+        true
+    }
+}
+
+impl Eq for RequestCost {}
+
+// function classifying a request into a named quota tier, or `None` to pass it
+// through unlimited. Absent (`None`) until a classifier is set.
+#[derive(Clone, Default)]
+struct Classifier(Option<Arc<dyn Fn(&Request<Body>) -> Option<&'static str> + Send + Sync>>);
+
+impl fmt::Debug for Classifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Classifier").finish()
+    }
+}
+
+impl PartialEq for Classifier {
+    fn eq(&self, _: &Self) -> bool {
+        // there is no easy way to tell two object equals.
+        true
+    }
+}
+
+impl Eq for Classifier {}
+
+/// A single named quota tier: its own limiter plus the burst size and period it
+/// was built from, kept so the `RateLimit-*` headers can report the right
+/// limit and reset for the tier a request fell into.
+#[derive(Debug, Clone)]
+pub struct Tier {
+    limiter: SharedRateLimiter,
+    burst_size: u32,
+    period: Duration,
+}
+
+impl Tier {
+    pub(crate) fn limiter(&self) -> &SharedRateLimiter {
+        &self.limiter
+    }
+
+    pub(crate) fn burst_size(&self) -> u32 {
+        self.burst_size
+    }
+
+    pub(crate) fn period(&self) -> Duration {
+        self.period
+    }
+}
+
 impl Default for ErrorHandler {
     fn default() -> Self {
         Self(Arc::new(|mut e| e.as_response()))
@@ -103,6 +197,29 @@ impl GovernorConfigBuilder {
         self.error_handler = ErrorHandler(Arc::new(func));
         self
     }
+
+    /// Set a function computing how many cells of the quota a request consumes.
+    ///
+    /// This lets expensive endpoints be charged more than cheap ones, e.g. a
+    /// bulk-import `POST` can cost ten cells while a `GET` costs one. A request
+    /// whose cost exceeds the configured burst size can never be served and is
+    /// rejected with [`GovernorError::InsufficientCapacity`].
+    ///
+    /// By default every request costs a single cell.
+    /// # Example
+    /// ```rust
+    /// # use http::Method;
+    /// # use tower_governor::governor::GovernorConfigBuilder;
+    /// GovernorConfigBuilder::default()
+    ///     .request_cost(|req| if req.method() == Method::POST { 10 } else { 1 });
+    /// ```
+    pub fn request_cost<F>(&mut self, func: F) -> &mut Self
+    where
+        F: Fn(&http::Request<axum::body::Body>) -> u32 + Send + Sync + 'static,
+    {
+        self.request_cost = RequestCost(Arc::new(func));
+        self
+    }
 }
 
 /// Sets the default Governor Config and defines all the different configuration functions
@@ -114,6 +231,11 @@ impl GovernorConfigBuilder {
             burst_size: DEFAULT_BURST_SIZE,
             methods: None,
             error_handler: ErrorHandler::default(),
+            request_cost: RequestCost::default(),
+            use_headers: false,
+            jitter: None,
+            tiers: Vec::new(),
+            classifier: Classifier::default(),
         }
     }
     /// Set the interval after which one element of the quota is replenished.
@@ -202,37 +324,209 @@ impl GovernorConfigBuilder {
         self
     }
 
+    /// Emit the standardized `RateLimit-Limit`, `RateLimit-Remaining` and
+    /// `RateLimit-Reset` headers on allowed responses so well-behaved clients
+    /// can see their remaining budget, not just on rejected (`429`) responses.
+    ///
+    /// By default these headers are not emitted.
+    pub fn use_headers(&mut self) -> &mut Self {
+        self.use_headers = true;
+        self
+    }
+
+    /// Add a randomized back-off of up to `max` to the reported wait time on
+    /// rejected responses. When many clients are throttled at the same instant
+    /// they otherwise receive an identical `retry-after` and retry in lockstep,
+    /// stampeding the quota as it replenishes; jitter spreads their retries out.
+    ///
+    /// The rejected wait time is reported in milliseconds, so a sub-second
+    /// `max` still spreads retries meaningfully. By default no jitter is
+    /// applied and the exact wait time is reported.
+    pub fn jitter(&mut self, max: Duration) -> &mut Self {
+        self.jitter = Some(Jitter::new(Duration::ZERO, max));
+        self
+    }
+
+    /// Register a named quota tier with its own period and burst size. Combined
+    /// with [`classifier`](Self::classifier), one middleware can apply different
+    /// limits to different kinds of traffic — e.g. a generous read tier and a
+    /// strict write tier. Registering the same name twice keeps the last quota.
+    ///
+    /// Tiers take effect once a [`classifier`](Self::classifier) is set: the
+    /// per-tier limiters then replace the single shared limiter. Without a
+    /// classifier, registered tiers are not used.
+    pub fn tier(
+        &mut self,
+        name: impl Into<String>,
+        period: Duration,
+        burst_size: u32,
+    ) -> &mut Self {
+        let name = name.into();
+        self.tiers.retain(|(existing, _, _)| existing != &name);
+        self.tiers.push((name, period, burst_size));
+        self
+    }
+
+    /// Set the function that picks which registered tier a request belongs to.
+    /// Returning `None` means the request is not limited and passes through, as
+    /// does returning a name that matches no registered [`tier`](Self::tier).
+    /// # Example
+    /// ```rust
+    /// # use http::Method;
+    /// # use tower_governor::governor::GovernorConfigBuilder;
+    /// GovernorConfigBuilder::default()
+    ///     .classifier(|req| match req.method() {
+    ///         &Method::GET => Some("read"),
+    ///         _ => Some("write"),
+    ///     });
+    /// ```
+    pub fn classifier<F>(&mut self, func: F) -> &mut Self
+    where
+        F: Fn(&http::Request<axum::body::Body>) -> Option<&'static str> + Send + Sync + 'static,
+    {
+        self.classifier = Classifier(Some(Arc::new(func)));
+        self
+    }
+
     /// Finish building the configuration and return the configuration for the middleware.
-    /// Returns `None` if either burst size or period interval are zero.
+    /// Returns `None` if the burst size or period interval of the default quota
+    /// or of any registered tier is zero.
     pub fn finish(&mut self) -> Option<GovernorConfig> {
         if self.burst_size != 0 && self.period.as_nanos() != 0 {
+            let mut tiers = HashMap::with_capacity(self.tiers.len());
+            for (name, period, burst_size) in &self.tiers {
+                if *burst_size == 0 || period.as_nanos() == 0 {
+                    return None;
+                }
+                tiers.insert(
+                    name.clone(),
+                    Tier {
+                        limiter: build_limiter(*period, *burst_size),
+                        burst_size: *burst_size,
+                        period: *period,
+                    },
+                );
+            }
             Some(GovernorConfig {
-                limiter: Arc::new(RateLimiter::direct(
-                    Quota::with_period(self.period)
-                        .unwrap()
-                        .allow_burst(NonZeroU32::new(self.burst_size).unwrap()),
-                )),
+                limiter: build_limiter(self.period, self.burst_size),
+                burst_size: self.burst_size,
+                period: self.period,
                 methods: self.methods.clone(),
                 error_handler: self.error_handler.clone(),
+                request_cost: self.request_cost.clone(),
+                use_headers: self.use_headers,
+                jitter: self.jitter,
+                tiers,
+                classifier: self.classifier.clone(),
             })
         } else {
             None
         }
     }
+
+    /// Finish building the configuration and return a [`GovernorConfigHandle`]
+    /// whose quota can be reloaded at runtime via
+    /// [`reload`](GovernorConfigHandle::reload).
+    /// Returns `None` under the same conditions as [`finish`](Self::finish).
+    pub fn finish_handle(&mut self) -> Option<GovernorConfigHandle> {
+        self.finish().map(|config| GovernorConfigHandle {
+            config: Arc::new(ArcSwap::from_pointee(config)),
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
 /// Configuration for the Governor middleware.
 pub struct GovernorConfig {
     limiter: SharedRateLimiter,
+    burst_size: u32,
+    period: Duration,
     methods: Option<Vec<Method>>,
     error_handler: ErrorHandler,
+    request_cost: RequestCost,
+    use_headers: bool,
+    jitter: Option<Jitter>,
+    tiers: HashMap<String, Tier>,
+    classifier: Classifier,
 }
 
 impl GovernorConfig {
     pub fn limiter(&self) -> &SharedRateLimiter {
         &self.limiter
     }
+
+    pub(crate) fn burst_size(&self) -> u32 {
+        self.burst_size
+    }
+
+    pub(crate) fn period(&self) -> Duration {
+        self.period
+    }
+
+    pub(crate) fn methods(&self) -> Option<&Vec<Method>> {
+        self.methods.as_ref()
+    }
+
+    pub(crate) fn uses_headers(&self) -> bool {
+        self.use_headers
+    }
+
+    pub(crate) fn jitter(&self) -> Option<Jitter> {
+        self.jitter
+    }
+
+    /// Whether named tiers are in use. When `true`, the classifier selects a
+    /// tier per request instead of using the single shared limiter.
+    pub(crate) fn is_tiered(&self) -> bool {
+        self.classifier.0.is_some()
+    }
+
+    /// Classify a request into a tier name, or `None` to pass it through.
+    pub(crate) fn classify(&self, req: &Request<Body>) -> Option<&'static str> {
+        self.classifier.0.as_ref().and_then(|c| c(req))
+    }
+
+    pub(crate) fn tier(&self, name: &str) -> Option<&Tier> {
+        self.tiers.get(name)
+    }
+
+    pub(crate) fn error_handler(&self) -> &(dyn Fn(GovernorError) -> Response<Body> + Send + Sync) {
+        &*self.error_handler.0
+    }
+
+    pub(crate) fn request_cost(&self) -> &(dyn Fn(&Request<Body>) -> u32 + Send + Sync) {
+        &*self.request_cost.0
+    }
+}
+
+/// A cloneable handle to a live [`GovernorConfig`] that can be swapped at
+/// runtime. Every [`GovernorLayer`] and [`Governor`] built from the handle
+/// shares the same underlying [`ArcSwap`], so [`reload`](Self::reload) adjusts
+/// the quota of an already-running service stack without rebuilding it.
+///
+/// A configuration from [`finish`](GovernorConfigBuilder::finish) carries a
+/// freshly constructed `RateLimiter`, so swapping one in resets the limiter
+/// state — the quota starts full under the new configuration. This is usually what operators want when loosening limits
+/// during maintenance or tightening them under attack.
+#[derive(Clone)]
+pub struct GovernorConfigHandle {
+    config: Arc<ArcSwap<GovernorConfig>>,
+}
+
+impl GovernorConfigHandle {
+    /// Atomically swap in a new configuration for every service sharing this
+    /// handle. The service stops using the previous limiter and its state.
+    pub fn reload(&self, config: GovernorConfig) {
+        self.config.store(Arc::new(config));
+    }
+
+    /// Build a [`GovernorLayer`] that tracks this handle, so reloads are
+    /// observed by the services it produces.
+    pub fn layer(&self) -> crate::GovernorLayer {
+        crate::GovernorLayer {
+            config: self.config.clone(),
+        }
+    }
 }
 
 impl Default for GovernorConfig {
@@ -255,6 +549,11 @@ impl GovernorConfig {
             burst_size: 2,
             methods: None,
             error_handler: ErrorHandler::default(),
+            request_cost: RequestCost::default(),
+            use_headers: false,
+            jitter: None,
+            tiers: Vec::new(),
+            classifier: Classifier::default(),
         }
         .finish()
         .unwrap()
@@ -266,35 +565,27 @@ impl GovernorConfig {
 /// https://stegosaurusdormant.com/understanding-derive-clone/
 #[derive(Debug)]
 pub struct Governor<S> {
-    pub limiter: SharedRateLimiter,
-    pub methods: Option<Vec<Method>>,
+    /// The live configuration, shared with the originating [`GovernorLayer`] and
+    /// any [`GovernorConfigHandle`] so reloads are observed per-request.
+    pub config: Arc<ArcSwap<GovernorConfig>>,
     pub inner: S,
-    error_handler: ErrorHandler,
 }
 
 impl<S: Clone> Clone for Governor<S> {
     fn clone(&self) -> Self {
         Self {
-            limiter: self.limiter.clone(),
-            methods: self.methods.clone(),
+            config: self.config.clone(),
             inner: self.inner.clone(),
-            error_handler: self.error_handler.clone(),
         }
     }
 }
 
 impl<S> Governor<S> {
-    /// Create new governor middleware factory from configuration.
-    pub fn new(inner: S, config: &GovernorConfig) -> Self {
+    /// Create new governor middleware factory from a live configuration.
+    pub fn new(inner: S, config: &Arc<ArcSwap<GovernorConfig>>) -> Self {
         Governor {
-            limiter: config.limiter.clone(),
-            methods: config.methods.clone(),
+            config: config.clone(),
             inner,
-            error_handler: config.error_handler.clone(),
         }
     }
-
-    pub(crate) fn error_handler(&self) -> &(dyn Fn(GovernorError) -> Response<Body> + Send + Sync) {
-        &*self.error_handler.0
-    }
 }
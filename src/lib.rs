@@ -7,6 +7,7 @@ pub mod errors;
 pub mod governor;
 use crate::governor::{Governor, GovernorConfig};
 use ::governor::clock::{Clock, DefaultClock};
+use arc_swap::ArcSwap;
 use axum::body::Body;
 pub use errors::GovernorError;
 use http::response::Response;
@@ -14,14 +15,38 @@ use http::response::Response;
 use http::request::Request;
 use http::HeaderMap;
 use pin_project::pin_project;
+use std::num::NonZeroU32;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::{future::Future, pin::Pin};
 use tower::{Layer, Service};
 
 /// The Layer type that implements tower::Layer and is passed into `.layer()`
+///
+/// The configuration lives behind an [`ArcSwap`] so it can be hot-reloaded at
+/// runtime; build one from [`GovernorConfigHandle::layer`] to keep a handle for
+/// later [`reload`](crate::governor::GovernorConfigHandle::reload) calls.
 pub struct GovernorLayer {
-    pub config: Arc<GovernorConfig>,
+    pub config: Arc<ArcSwap<GovernorConfig>>,
+}
+
+impl GovernorLayer {
+    /// Build a layer from a static [`GovernorConfig`]. Use this for the common
+    /// case where the quota never changes at runtime; wrap the config in an
+    /// [`ArcSwap`] so the hot-reload machinery still applies. For a reloadable
+    /// quota, build the layer from a
+    /// [`GovernorConfigHandle`](crate::governor::GovernorConfigHandle) instead.
+    pub fn new(config: GovernorConfig) -> Self {
+        Self {
+            config: Arc::new(ArcSwap::from_pointee(config)),
+        }
+    }
+}
+
+impl From<GovernorConfig> for GovernorLayer {
+    fn from(config: GovernorConfig) -> Self {
+        Self::new(config)
+    }
 }
 
 impl<S> Layer<S> for GovernorLayer {
@@ -41,9 +66,15 @@ impl Clone for GovernorLayer {
     }
 }
 // Implement tower::Service for Governor
-impl<S, ReqBody> Service<Request<ReqBody>> for Governor<S>
+//
+// This is specialized to axum's [`Body`] rather than being generic over the
+// request body type. The `request_cost` and `classifier` callbacks inspect a
+// `Request<Body>`, so the middleware can only be layered over a service whose
+// request body is `Body` — which is the case for every axum router. This is an
+// intentional narrowing from a fully body-generic middleware.
+impl<S> Service<Request<Body>> for Governor<S>
 where
-    S: Service<Request<ReqBody>, Response = Response<Body>>,
+    S: Service<Request<Body>, Response = Response<Body>>,
 {
     type Response = S::Response;
     type Error = S::Error;
@@ -53,38 +84,140 @@ where
         self.inner.poll_ready(cx)
     }
 
-    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
-        if let Some(configured_methods) = &self.methods {
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        // Read the live configuration for this request so runtime reloads of the
+        // quota take effect without rebuilding the service stack.
+        let config = self.config.load();
+
+        if let Some(configured_methods) = config.methods() {
             if !configured_methods.contains(req.method()) {
                 // The request method is not configured, we're ignoring this one.
                 let future = self.inner.call(req);
                 return ResponseFuture {
-                    inner: Kind::Passthrough { future },
+                    inner: Kind::Passthrough {
+                        future,
+                        headers: None,
+                    },
                 };
             }
         }
-        match self.limiter.check() {
-            Ok(_) => {
+
+        // Select the limiter for this request. With named tiers the classifier
+        // picks one (returning `None`, or a name matching no tier, passes the
+        // request through unlimited); otherwise the single shared limiter is used.
+        let selected = if config.is_tiered() {
+            config
+                .classify(&req)
+                .and_then(|name| config.tier(name))
+                .map(|tier| (tier.limiter(), tier.burst_size(), tier.period()))
+        } else {
+            Some((config.limiter(), config.burst_size(), config.period()))
+        };
+        let (limiter, burst_size, period) = match selected {
+            Some(selected) => selected,
+            None => {
+                let future = self.inner.call(req);
+                return ResponseFuture {
+                    inner: Kind::Passthrough {
+                        future,
+                        headers: None,
+                    },
+                };
+            }
+        };
+
+        // A cost of zero consumes nothing from the quota, so the request passes
+        // through untouched. Any positive cost is charged atomically via `check_n`.
+        let cost = config.request_cost()(&req);
+        let cost = match NonZeroU32::new(cost) {
+            Some(cost) => cost,
+            None => {
+                let future = self.inner.call(req);
+                return ResponseFuture {
+                    inner: Kind::Passthrough {
+                        future,
+                        headers: None,
+                    },
+                };
+            }
+        };
+
+        match limiter.check_n(cost) {
+            Ok(Ok(snapshot)) => {
+                // On the allowed path, optionally report the client's budget via
+                // the standardized IETF draft `RateLimit-*` headers.
+                let headers = config.uses_headers().then(|| {
+                    let mut headers = HeaderMap::new();
+                    headers.insert("ratelimit-limit", burst_size.into());
+                    headers.insert(
+                        "ratelimit-remaining",
+                        snapshot.remaining_burst_capacity().into(),
+                    );
+                    // Round the reset up to whole seconds so a sub-second
+                    // period (the default is 500ms) never reports `0`, which
+                    // would tell clients the window had already reset.
+                    let reset = (period.as_millis() as u64).div_ceil(1000).max(1);
+                    headers.insert("ratelimit-reset", reset.into());
+                    headers
+                });
                 let future = self.inner.call(req);
                 ResponseFuture {
-                    inner: Kind::Passthrough { future },
+                    inner: Kind::Passthrough { future, headers },
+                }
+            }
+
+            Err(_) => {
+                // The request costs more cells than the burst size holds, so it
+                // can never be satisfied. Surface this distinctly from a 429.
+                #[cfg(feature = "tracing")]
+                {
+                    tracing::info!(
+                        "Request cost {} exceeds burst size {}",
+                        cost.get(),
+                        burst_size
+                    );
+                }
+
+                let error_response =
+                    config.error_handler()(GovernorError::InsufficientCapacity {
+                        cost: cost.get(),
+                        burst_size,
+                    });
+
+                ResponseFuture {
+                    inner: Kind::Error {
+                        error_response: Some(error_response),
+                    },
                 }
             }
 
-            Err(negative) => {
-                let wait_time = negative
-                    .wait_time_from(DefaultClock::default().now())
-                    .as_secs();
+            Ok(Err(negative)) => {
+                let base_wait = negative.wait_time_from(DefaultClock::default().now());
+                // Spread retries when a randomized back-off is configured so
+                // throttled clients don't all retry in lockstep. `governor`
+                // only implements `Duration + Jitter`, so the base wait comes
+                // first.
+                let wait = match config.jitter() {
+                    Some(jitter) => base_wait + jitter,
+                    None => base_wait,
+                };
+                // The custom `x-ratelimit-after` header and the error's
+                // `wait_time` carry milliseconds, so a sub-second jitter window
+                // isn't truncated away. The standard `Retry-After` header is
+                // defined as delta-seconds (RFC 7231 §7.1.3) and has no
+                // sub-second form, so it is rounded up to whole seconds.
+                let wait_time = wait.as_millis() as u64;
+                let retry_after = wait_time.div_ceil(1000);
 
                 #[cfg(feature = "tracing")]
                 {
-                    tracing::info!("Rate limit exceeded, quota reset in {}s", &wait_time);
+                    tracing::info!("Rate limit exceeded, quota reset in {}ms", &wait_time);
                 }
                 let mut headers = HeaderMap::new();
                 headers.insert("x-ratelimit-after", wait_time.into());
-                headers.insert("retry-after", wait_time.into());
+                headers.insert("retry-after", retry_after.into());
 
-                let error_response = self.error_handler()(GovernorError::TooManyRequests {
+                let error_response = config.error_handler()(GovernorError::TooManyRequests {
                     wait_time,
                     headers: Some(headers),
                 });
@@ -113,6 +246,9 @@ enum Kind<F> {
     Passthrough {
         #[pin]
         future: F,
+        /// `RateLimit-*` headers to inject into the downstream response once it
+        /// resolves. `None` when the response should be forwarded untouched.
+        headers: Option<HeaderMap>,
     },
     Error {
         error_response: Option<Response<Body>>,
@@ -127,7 +263,16 @@ where
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         match self.project().inner.project() {
-            KindProj::Passthrough { future } => future.poll(cx),
+            KindProj::Passthrough { future, headers } => {
+                let mut response = match future.poll(cx) {
+                    Poll::Ready(response) => response?,
+                    Poll::Pending => return Poll::Pending,
+                };
+                if let Some(headers) = headers.take() {
+                    response.headers_mut().extend(headers);
+                }
+                Poll::Ready(Ok(response))
+            }
             KindProj::Error { error_response } => Poll::Ready(Ok(error_response.take().expect("
                 <Governor as Service<Request<_>>>::call must produce Response<String> when GovernorError occurs.
             "))),
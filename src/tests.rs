@@ -0,0 +1,164 @@
+use crate::governor::GovernorConfigBuilder;
+use crate::GovernorLayer;
+use axum::body::Body;
+use http::{Method, Request, Response, StatusCode};
+use std::convert::Infallible;
+use std::time::Duration;
+use tower::{service_fn, Layer, Service, ServiceExt};
+
+/// A trivial inner service that always answers `200 OK`.
+async fn ok(_req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    Ok(Response::new(Body::empty()))
+}
+
+/// Drive one request through a ready service and unwrap its response.
+async fn send<S>(service: &mut S, req: Request<Body>) -> Response<Body>
+where
+    S: Service<Request<Body>, Response = Response<Body>, Error = Infallible>,
+{
+    service.ready().await.unwrap().call(req).await.unwrap()
+}
+
+fn get() -> Request<Body> {
+    Request::builder()
+        .method(Method::GET)
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn post() -> Request<Body> {
+    Request::builder()
+        .method(Method::POST)
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn cost_exceeding_burst_is_rejected_with_payload_too_large() {
+    let config = GovernorConfigBuilder::default()
+        .burst_size(5)
+        .request_cost(|_| 10)
+        .finish()
+        .unwrap();
+    let mut service = GovernorLayer::new(config).layer(service_fn(ok));
+
+    let res = send(&mut service, get()).await;
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn use_headers_emits_ratelimit_headers_on_success() {
+    let config = GovernorConfigBuilder::default()
+        .burst_size(5)
+        .use_headers()
+        .finish()
+        .unwrap();
+    let mut service = GovernorLayer::new(config).layer(service_fn(ok));
+
+    let res = send(&mut service, get()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let headers = res.headers();
+    assert!(headers.contains_key("ratelimit-limit"));
+    assert!(headers.contains_key("ratelimit-remaining"));
+    assert!(headers.contains_key("ratelimit-reset"));
+}
+
+#[tokio::test]
+async fn reload_changes_the_effective_limit() {
+    // A single cell that never replenishes during the test.
+    let handle = GovernorConfigBuilder::default()
+        .burst_size(1)
+        .per_second(3600)
+        .finish_handle()
+        .unwrap();
+    let mut service = handle.layer().layer(service_fn(ok));
+
+    assert_eq!(send(&mut service, get()).await.status(), StatusCode::OK);
+    assert_eq!(
+        send(&mut service, get()).await.status(),
+        StatusCode::TOO_MANY_REQUESTS
+    );
+
+    // Loosen the quota: the swap installs a fresh limiter with a full burst.
+    let relaxed = GovernorConfigBuilder::default()
+        .burst_size(5)
+        .per_second(3600)
+        .finish()
+        .unwrap();
+    handle.reload(relaxed);
+
+    assert_eq!(send(&mut service, get()).await.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn jitter_keeps_reported_wait_within_the_configured_window() {
+    let config = GovernorConfigBuilder::default()
+        .burst_size(1)
+        .per_millisecond(500)
+        .jitter(Duration::from_millis(100))
+        .finish()
+        .unwrap();
+    let mut service = GovernorLayer::new(config).layer(service_fn(ok));
+
+    assert_eq!(send(&mut service, get()).await.status(), StatusCode::OK);
+
+    let res = send(&mut service, get()).await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    let header = |name| -> u64 {
+        res.headers()
+            .get(name)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap()
+    };
+    // The fine-grained value lives in the custom header in milliseconds:
+    // base wait (<= 500ms period) plus jitter (<= 100ms).
+    assert!(
+        header("x-ratelimit-after") <= 600,
+        "x-ratelimit-after out of window"
+    );
+    // `Retry-After` is whole seconds rounded up, so a sub-second wait is 1.
+    assert_eq!(header("retry-after"), 1);
+}
+
+#[tokio::test]
+async fn classifier_none_passes_through_unlimited() {
+    let config = GovernorConfigBuilder::default()
+        .burst_size(1)
+        .tier("write", Duration::from_secs(3600), 1)
+        .classifier(|_| None)
+        .finish()
+        .unwrap();
+    let mut service = GovernorLayer::new(config).layer(service_fn(ok));
+
+    // Nothing is classified, so every request bypasses the limiter.
+    for _ in 0..10 {
+        assert_eq!(send(&mut service, get()).await.status(), StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn classifier_routes_requests_to_their_named_tier() {
+    let config = GovernorConfigBuilder::default()
+        .tier("read", Duration::from_secs(3600), 5)
+        .tier("write", Duration::from_secs(3600), 1)
+        .classifier(|req| match *req.method() {
+            Method::GET => Some("read"),
+            _ => Some("write"),
+        })
+        .finish()
+        .unwrap();
+    let mut service = GovernorLayer::new(config).layer(service_fn(ok));
+
+    // The strict write tier exhausts after a single request.
+    assert_eq!(send(&mut service, post()).await.status(), StatusCode::OK);
+    assert_eq!(
+        send(&mut service, post()).await.status(),
+        StatusCode::TOO_MANY_REQUESTS
+    );
+
+    // The generous read tier is unaffected by the exhausted write tier.
+    assert_eq!(send(&mut service, get()).await.status(), StatusCode::OK);
+}